@@ -2,6 +2,9 @@
 //!
 //! Helper library for manipulating 2d grid.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::iter::FromIterator;
 use std::fmt;
 
@@ -153,6 +156,64 @@ impl Grid {
         }
     }
 
+    /// Runs a breadth-first search from all `sources` at once over tiles for which `passable`
+    /// returns true, and records the minimum step count to reach every cell (`None` where
+    /// unreachable). Unlike [`fill`](#method.fill), which explores with a LIFO stack, this walks
+    /// a proper FIFO queue so cells are visited in increasing distance order. Set `diagonal` to
+    /// `true` to step through the 8-neighbourhood instead of the 4-neighbourhood.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let grid = Grid::from("...\n.#.\n...");
+    ///
+    /// let distances = grid.distance_map(&[(0, 0)], |c| c != '#', false);
+    ///
+    /// assert_eq!(distances[0][0], Some(0));
+    /// assert_eq!(distances[2][2], Some(4));
+    /// assert_eq!(distances[1][1], None);
+    /// ```
+    pub fn distance_map(
+        &self,
+        sources: &[(usize, usize)],
+        passable: impl Fn(char) -> bool,
+        diagonal: bool,
+    ) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.width()]; self.height()];
+        let mut frontier = VecDeque::new();
+        for &(x, y) in sources {
+            if x >= self.width() || !self.get(x, y).is_some_and(&passable) {
+                continue;
+            }
+            if distances[y][x].is_none() {
+                distances[y][x] = Some(0);
+                frontier.push_back((x, y));
+            }
+        }
+        while let Some((cx, cy)) = frontier.pop_front() {
+            let dist = distances[cy][cx].unwrap();
+            let neighbours = if diagonal {
+                self.neighbours(cx, cy)
+            } else {
+                self.orthogonal_neighbours(cx, cy)
+            };
+            for (nx, ny) in neighbours {
+                if distances[ny][nx].is_some() {
+                    continue;
+                }
+                if let Some(c) = self.get(nx, ny) {
+                    if passable(c) {
+                        distances[ny][nx] = Some(dist + 1);
+                        frontier.push_back((nx, ny));
+                    }
+                }
+            }
+        }
+        distances
+    }
+
     /// Counts tiles with specified character.
     ///
     /// # Examples
@@ -227,6 +288,348 @@ impl Grid {
             None
         }
     }
+
+    /// Returns coordinates of the 4-connected (orthogonal) neighbour tiles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let grid = Grid::new(10, 10);
+    ///
+    /// assert_eq!(grid.orthogonal_neighbours(5, 5), vec![(5, 4), (6, 5), (5, 6), (4, 5)]);
+    /// assert_eq!(grid.orthogonal_neighbours(0, 0), vec![(1, 0), (0, 1)]);
+    /// assert_eq!(grid.orthogonal_neighbours(100, 100), vec![]);
+    /// ```
+    pub fn orthogonal_neighbours(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut nb = Vec::new();
+        let height = self.height();
+        let width = self.width();
+        if x >= width || y >= height {
+            return nb;
+        }
+        if y > 0 {
+            nb.push((x, y - 1));
+        }
+        if x < width - 1 {
+            nb.push((x + 1, y));
+        }
+        if y < height - 1 {
+            nb.push((x, y + 1));
+        }
+        if x > 0 {
+            nb.push((x - 1, y));
+        }
+        nb
+    }
+
+    /// Returns every 4-connected group of cells holding `tile`, found via a flood fill over a
+    /// visited bitmap so each cell is touched at most once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let grid = Grid::from("#.#.\n#.##\n####");
+    ///
+    /// let mut regions = grid.regions('.');
+    /// regions.sort_by_key(|r| r.len());
+    ///
+    /// assert_eq!(regions, vec![vec![(3, 0)], vec![(1, 0), (1, 1)]]);
+    /// ```
+    pub fn regions(&self, tile: char) -> Vec<Vec<(usize, usize)>> {
+        let mut visited = vec![false; self.tiles.len()];
+        let mut regions = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let pos = self.cord_to_pos(x, y).unwrap();
+                if visited[pos] || self.tiles[pos] != tile {
+                    continue;
+                }
+                let mut region = Vec::new();
+                let mut frontier = vec![(x, y)];
+                visited[pos] = true;
+                while let Some((cx, cy)) = frontier.pop() {
+                    region.push((cx, cy));
+                    for (nx, ny) in self.orthogonal_neighbours(cx, cy) {
+                        let npos = self.cord_to_pos(nx, ny).unwrap();
+                        if !visited[npos] && self.tiles[npos] == tile {
+                            visited[npos] = true;
+                            frontier.push((nx, ny));
+                        }
+                    }
+                }
+                regions.push(region);
+            }
+        }
+        regions
+    }
+
+    /// Returns a new grid where each tile is stamped with the char of the seed (from `seeds`)
+    /// closest to it by Manhattan distance, or `'.'` when two or more seeds are tied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let grid = Grid::new(5, 1);
+    /// let labeled = grid.nearest_seeds(&[(0, 0, 'A'), (4, 0, 'B')]);
+    ///
+    /// assert_eq!(labeled.to_string(), "AA.BB\n");
+    /// ```
+    pub fn nearest_seeds(&self, seeds: &[(usize, usize, char)]) -> Grid {
+        let mut labeled = Grid::filled_with(self.width(), self.height(), '.');
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let mut best_dist = usize::MAX;
+                let mut best_char = None;
+                let mut tied = false;
+                for &(sx, sy, c) in seeds {
+                    let dist = manhattan_distance(x, y, sx, sy);
+                    if dist < best_dist {
+                        best_dist = dist;
+                        best_char = Some(c);
+                        tied = false;
+                    } else if dist == best_dist {
+                        tied = true;
+                    }
+                }
+                if !tied {
+                    if let Some(c) = best_char {
+                        labeled.set(x, y, c);
+                    }
+                }
+            }
+        }
+        labeled
+    }
+
+    /// Computes the territory size of every seed via [`nearest_seeds`](#method.nearest_seeds),
+    /// excluding any seed whose territory touches the grid border, since such a territory is
+    /// unbounded and its size is meaningless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let grid = Grid::new(10, 10);
+    /// let seeds = [(1, 1, 'A'), (1, 6, 'B'), (8, 3, 'C'), (3, 4, 'D'), (5, 5, 'E'), (8, 9, 'F')];
+    ///
+    /// let sizes = grid.finite_region_sizes(&seeds);
+    ///
+    /// assert_eq!(sizes.get(&'E'), Some(&17));
+    /// assert_eq!(sizes.get(&'A'), None);
+    /// ```
+    pub fn finite_region_sizes(&self, seeds: &[(usize, usize, char)]) -> HashMap<char, usize> {
+        let labeled = self.nearest_seeds(seeds);
+        let width = labeled.width();
+        let height = labeled.height();
+        let mut sizes = HashMap::new();
+        let mut infinite = HashSet::new();
+        for y in 0..height {
+            for x in 0..width {
+                let c = labeled.get(x, y).unwrap();
+                if !seeds.iter().any(|&(_, _, sc)| sc == c) {
+                    continue;
+                }
+                *sizes.entry(c).or_insert(0) += 1;
+                if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                    infinite.insert(c);
+                }
+            }
+        }
+        for c in infinite {
+            sizes.remove(&c);
+        }
+        sizes
+    }
+
+    /// Finds every region of `tile` via [`regions`](#method.regions) and overwrites any region
+    /// smaller than `min_size` with `replace_with`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let mut grid = Grid::from("#.#.\n#.##\n####");
+    ///
+    /// grid.cull_regions('.', 2, '#');
+    ///
+    /// assert_eq!(grid.to_string(), "#.##\n#.##\n####\n");
+    /// ```
+    pub fn cull_regions(&mut self, tile: char, min_size: usize, replace_with: char) {
+        for region in self.regions(tile) {
+            if region.len() < min_size {
+                for (x, y) in region {
+                    self.set(x, y, replace_with);
+                }
+            }
+        }
+    }
+
+    /// Runs `phases` in order, each phase moving every tile holding `mover` one step in
+    /// `direction` when its destination (as read from a snapshot taken before the phase) holds
+    /// `empty`. Because every move in a phase is computed from that shared snapshot and only
+    /// applied afterwards, movers within a phase never see each other's moves and can never
+    /// collide into the same destination. When `wrap` is `true` a move past the grid edge wraps
+    /// around to the opposite side, otherwise it is discarded. Returns whether anything moved, so
+    /// callers can loop `step_movers` until the grid reaches a stable state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// // '>' movers shift right, then 'v' movers shift down, each phase frozen on its own snapshot
+    /// let mut grid = Grid::from("..\n>.\n.v");
+    ///
+    /// let moved = grid.step_movers(&[('>', (1, 0)), ('v', (0, 1))], '.', false);
+    ///
+    /// assert!(moved);
+    /// assert_eq!(grid.to_string(), "..\n.>\n.v\n");
+    /// ```
+    pub fn step_movers(&mut self, phases: &[(char, (i32, i32))], empty: char, wrap: bool) -> bool {
+        let mut moved = false;
+        let width = self.width() as i32;
+        let height = self.height() as i32;
+        for &(mover, (dx, dy)) in phases {
+            let snapshot = self.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    if snapshot.get(x as usize, y as usize) != Some(mover) {
+                        continue;
+                    }
+                    let (mut tx, mut ty) = (x + dx, y + dy);
+                    if wrap {
+                        tx = (tx + width) % width;
+                        ty = (ty + height) % height;
+                    } else if tx < 0 || tx >= width || ty < 0 || ty >= height {
+                        continue;
+                    }
+                    if snapshot.get(tx as usize, ty as usize) == Some(empty) {
+                        self.set(x as usize, y as usize, empty);
+                        self.set(tx as usize, ty as usize, mover);
+                        moved = true;
+                    }
+                }
+            }
+        }
+        moved
+    }
+
+    /// Fills every tile with a character chosen by weighted random pick, deterministic for a
+    /// given `seed`. Weights don't need to sum to 1, they are normalized internally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// let mut grid = Grid::new(4, 4);
+    /// grid.randomize(&[('#', 0.45), ('.', 0.55)], 1);
+    ///
+    /// let mut same_seed = Grid::new(4, 4);
+    /// same_seed.randomize(&[('#', 0.45), ('.', 0.55)], 1);
+    ///
+    /// assert_eq!(grid.count('#') + grid.count('.'), 16);
+    /// assert_eq!(grid.to_string(), same_seed.to_string());
+    /// ```
+    pub fn randomize(&mut self, chars: &[(char, f64)], seed: u64) {
+        let total: f64 = chars.iter().map(|(_, weight)| weight).sum();
+        let mut rng = SplitMix64::new(seed);
+        for tile in self.tiles.iter_mut() {
+            let mut roll = rng.next_f64() * total;
+            let mut chosen = chars.last().map(|(c, _)| *c);
+            for (c, weight) in chars {
+                if roll < *weight {
+                    chosen = Some(*c);
+                    break;
+                }
+                roll -= weight;
+            }
+            if let Some(c) = chosen {
+                *tile = c;
+            }
+        }
+    }
+
+    /// Advances the grid one cellular-automaton step. `rules` receives the current character of
+    /// a tile together with the counts of its 8 neighbours grouped by character, and returns the
+    /// character the tile should become.
+    ///
+    /// The step is double-buffered: `rules` is evaluated against a snapshot of the grid taken
+    /// before the step, and the results are only written back once every tile has been computed.
+    /// This means the order in which tiles are visited never affects the outcome.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grid::Grid;
+    ///
+    /// // a tile becomes (or stays) wall with >= 5 wall neighbours, otherwise it becomes floor
+    /// let mut grid = Grid::from("#####\n#...#\n#...#\n#...#\n#####");
+    ///
+    /// grid.step_automaton(|_tile, neighbours| {
+    ///     let walls = *neighbours.get(&'#').unwrap_or(&0);
+    ///     if walls >= 5 {
+    ///         '#'
+    ///     } else {
+    ///         '.'
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(grid.to_string(), ".....\n.#.#.\n.....\n.#.#.\n.....\n");
+    /// ```
+    pub fn step_automaton<F>(&mut self, mut rules: F)
+    where
+        F: FnMut(char, &HashMap<char, u8>) -> char,
+    {
+        let snapshot = self.clone();
+        for y in 0..snapshot.height() {
+            for x in 0..snapshot.width() {
+                let mut counts = HashMap::new();
+                for (nx, ny) in snapshot.neighbours(x, y) {
+                    if let Some(c) = snapshot.get(nx, ny) {
+                        *counts.entry(c).or_insert(0) += 1;
+                    }
+                }
+                let tile = snapshot.get(x, y).unwrap();
+                self.set(x, y, rules(tile, &counts));
+            }
+        }
+    }
+}
+
+/// Manhattan distance between two grid coordinates, used by [`Grid::nearest_seeds`].
+fn manhattan_distance(ax: usize, ay: usize, bx: usize, by: usize) -> usize {
+    ax.abs_diff(bx) + ay.abs_diff(by)
+}
+
+/// Deterministic, dependency-free pseudo-random generator (splitmix64) used by
+/// [`Grid::randomize`].
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
 }
 
 impl fmt::Display for Grid {
@@ -271,4 +674,183 @@ mod tests {
         assert_eq!(grid.width(), 5);
         assert_eq!(grid.height(), 8);
     }
+
+    #[test]
+    fn should_randomize_deterministically_for_same_seed() {
+        let mut a = Grid::new(6, 6);
+        a.randomize(&[('#', 0.4), ('.', 0.6)], 7);
+        let mut b = Grid::new(6, 6);
+        b.randomize(&[('#', 0.4), ('.', 0.6)], 7);
+
+        assert_eq!(a.to_string(), b.to_string());
+        assert_eq!(a.count('#') + a.count('.'), 36);
+    }
+
+    #[test]
+    fn should_randomize_differently_for_different_seeds() {
+        let mut a = Grid::new(10, 10);
+        a.randomize(&[('#', 0.5), ('.', 0.5)], 1);
+        let mut b = Grid::new(10, 10);
+        b.randomize(&[('#', 0.5), ('.', 0.5)], 2);
+
+        assert_ne!(a.to_string(), b.to_string());
+    }
+
+    #[test]
+    fn should_smooth_noise_with_automaton_rule() {
+        // a lone floor tile surrounded by 8 walls should turn into a wall
+        let mut grid = Grid::from("#####\n#####\n##.##\n#####\n#####");
+
+        grid.step_automaton(|_tile, neighbours| {
+            let walls = *neighbours.get(&'#').unwrap_or(&0);
+            if walls >= 5 {
+                '#'
+            } else {
+                '.'
+            }
+        });
+
+        assert_eq!(grid.count('#'), 21);
+    }
+
+    #[test]
+    fn should_compute_step_automaton_from_snapshot_not_partial_results() {
+        // a tile becomes wall iff it has a wall neighbour; read from the pre-step snapshot only
+        // (1, 0) sees the wall at (0, 0), while an in-place scan would clear (0, 0) first
+        let mut grid = Grid::from("#...");
+
+        grid.step_automaton(|_tile, neighbours| if neighbours.contains_key(&'#') { '#' } else { '.' });
+
+        assert_eq!(grid.to_string(), ".#..\n");
+    }
+
+    #[test]
+    fn should_find_orthogonal_neighbours() {
+        let grid = Grid::new(3, 3);
+        assert_eq!(grid.orthogonal_neighbours(1, 1), vec![(1, 0), (2, 1), (1, 2), (0, 1)]);
+        assert_eq!(grid.orthogonal_neighbours(0, 0), vec![(1, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn should_find_4_connected_regions() {
+        let grid = Grid::from("#.#.\n#.##\n####");
+
+        let mut regions = grid.regions('.');
+        regions.sort_by_key(|r| r.len());
+
+        assert_eq!(regions, vec![vec![(3, 0)], vec![(1, 0), (1, 1)]]);
+    }
+
+    #[test]
+    fn should_not_merge_diagonally_touching_regions() {
+        let grid = Grid::from(".#\n#.");
+
+        assert_eq!(grid.regions('.').len(), 2);
+    }
+
+    #[test]
+    fn should_cull_regions_smaller_than_min_size() {
+        let mut grid = Grid::from("#.#.\n#.##\n####");
+
+        grid.cull_regions('.', 2, '#');
+
+        assert_eq!(grid.to_string(), "#.##\n#.##\n####\n");
+    }
+
+    #[test]
+    fn should_label_tiles_by_nearest_seed() {
+        let grid = Grid::new(5, 1);
+
+        let labeled = grid.nearest_seeds(&[(0, 0, 'A'), (4, 0, 'B')]);
+
+        assert_eq!(labeled.to_string(), "AA.BB\n");
+    }
+
+    #[test]
+    fn should_exclude_infinite_regions_from_finite_region_sizes() {
+        let grid = Grid::new(10, 10);
+        let seeds = [(1, 1, 'A'), (1, 6, 'B'), (8, 3, 'C'), (3, 4, 'D'), (5, 5, 'E'), (8, 9, 'F')];
+
+        let sizes = grid.finite_region_sizes(&seeds);
+
+        assert_eq!(sizes.get(&'E'), Some(&17));
+        assert_eq!(sizes.get(&'A'), None);
+    }
+
+    #[test]
+    fn should_move_each_phase_from_a_frozen_snapshot() {
+        let mut grid = Grid::from("..\n>.\n.v");
+
+        let moved = grid.step_movers(&[('>', (1, 0)), ('v', (0, 1))], '.', false);
+
+        assert!(moved);
+        assert_eq!(grid.to_string(), "..\n.>\n.v\n");
+    }
+
+    #[test]
+    fn should_not_move_mover_into_occupied_tile() {
+        let mut grid = Grid::from(">>.");
+
+        let moved = grid.step_movers(&[('>', (1, 0))], '.', false);
+
+        assert!(moved);
+        assert_eq!(grid.to_string(), ">.>\n");
+    }
+
+    #[test]
+    fn should_report_no_movement_once_stable() {
+        let mut grid = Grid::from(">>>");
+
+        let moved = grid.step_movers(&[('>', (1, 0))], '.', false);
+
+        assert!(!moved);
+    }
+
+    #[test]
+    fn should_wrap_movers_around_grid_edges_when_enabled() {
+        let mut grid = Grid::from(".>");
+
+        grid.step_movers(&[('>', (1, 0))], '.', true);
+
+        assert_eq!(grid.to_string(), ">.\n");
+    }
+
+    #[test]
+    fn should_compute_bfs_distances_around_impassable_tiles() {
+        let grid = Grid::from("...\n.#.\n...");
+
+        let distances = grid.distance_map(&[(0, 0)], |c| c != '#', false);
+
+        assert_eq!(distances[0][0], Some(0));
+        assert_eq!(distances[2][2], Some(4));
+        assert_eq!(distances[1][1], None);
+    }
+
+    #[test]
+    fn should_take_shortest_distance_among_multiple_sources() {
+        let grid = Grid::from(".....");
+
+        let distances = grid.distance_map(&[(0, 0), (4, 0)], |_| true, false);
+
+        assert_eq!(distances[0], vec![Some(0), Some(1), Some(2), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn should_use_8_connectivity_when_diagonal_is_enabled() {
+        let grid = Grid::new(3, 3);
+
+        let distances = grid.distance_map(&[(0, 0)], |_| true, true);
+
+        assert_eq!(distances[2][2], Some(2));
+    }
+
+    #[test]
+    fn should_ignore_out_of_bounds_sources() {
+        let grid = Grid::new(3, 3);
+
+        let distances = grid.distance_map(&[(100, 100), (0, 0)], |_| true, false);
+
+        assert_eq!(distances[0][0], Some(0));
+        assert_eq!(distances[2][2], Some(4));
+    }
 }